@@ -1,21 +1,36 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use dashmap::DashMap;
 use smallvec::SmallVec;
 use parking_lot::RwLock;
 use std::sync::atomic::{AtomicU64, AtomicU32, Ordering};
 use std::sync::Arc;
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console)]
     fn log(s: &str);
-    
+
     #[wasm_bindgen(js_namespace = ["window", "performance"])]
     fn now() -> f64;
 }
 
+// Off the wasm target (e.g. `cargo test` on the host) the JS imports do not
+// exist, so fall back to plain stubs that keep the matching logic runnable.
+#[cfg(not(target_arch = "wasm32"))]
+fn log(s: &str) {
+    println!("{}", s);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> f64 {
+    0.0
+}
+
 macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
@@ -32,6 +47,9 @@ pub enum OrderSide {
 pub enum OrderType {
     Market = "Market",
     Limit = "Limit",
+    ImmediateOrCancel = "ImmediateOrCancel",
+    FillOrKill = "FillOrKill",
+    PostOnly = "PostOnly",
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,61 +101,205 @@ pub struct Trade {
     pub timestamp: f64,
 }
 
+/// Deterministic rejection reasons returned to callers instead of silently
+/// rounding a malformed order into the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderError {
+    OrderInvalidTick,
+    OrderInvalidLot,
+    OrderBelowMinimum,
+    PostOnlyWouldCross,
+    FillOrKillUnfillable,
+}
+
 type PriceLevel = SmallVec<[Order; 8]>;
 
+/// A resting conditional order. Buy-stops fire once `last_price` reaches or
+/// rises above `trigger_price`; sell-stops fire once it reaches or falls below.
+/// A `limit_price` of zero converts to a market order on trigger, otherwise to
+/// a limit order at that price.
+#[derive(Debug, Clone)]
+struct StopOrder {
+    id: u64,
+    side: OrderSide,
+    trigger_price: f64,
+    limit_price: f64,
+    quantity: f64,
+    user_id: u32,
+}
+
+/// Maximum number of retained level deltas. A client that falls further behind
+/// than this must re-sync through `get_checkpoint`; `poll_updates` reports this
+/// with a `must_resync` flag once the buffer has wrapped past the client.
+const UPDATE_RING_CAPACITY: usize = 4096;
+
+/// A single aggregated level change, tagged with the book's monotonic write
+/// version so clients can apply deltas in order. A `new_total_qty` of zero
+/// means the level was deleted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelUpdate {
+    pub side: OrderSide,
+    pub price: f64,
+    pub new_total_qty: f64,
+    pub write_version: u64,
+}
+
+/// Result of a `poll_updates` call. When the caller's `since_version` predates
+/// the oldest delta still retained in the ring buffer, the intervening deltas
+/// have been evicted and `must_resync` is set: the client cannot apply the
+/// partial batch and must re-sync through `get_checkpoint`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateBatch {
+    pub must_resync: bool,
+    pub updates: Vec<LevelUpdate>,
+}
+
 #[derive(Debug)]
 pub struct OrderBook {
     symbol: String,
-    bids: DashMap<u64, PriceLevel>, // price -> orders
-    asks: DashMap<u64, PriceLevel>, // price -> orders
+    tick_size: f64,
+    lot_size: f64,
+    min_size: f64,
+    // Price-ordered ladders keyed by integer ticks. Ascending iteration gives
+    // asks best-first; bids are read back-to-front for best-first. The best
+    // price sits at one end of each map (front for asks, back for bids), so
+    // `update_best_prices` is a cheap end lookup rather than a full scan.
+    bids: RwLock<BTreeMap<u64, PriceLevel>>, // tick -> orders
+    asks: RwLock<BTreeMap<u64, PriceLevel>>, // tick -> orders
     orders: DashMap<u64, Order>,
     next_trade_id: AtomicU64,
+    // Cumulative traded volume in integer lots; converted to base units with
+    // `lots_to_qty` when exposed to callers.
     total_volume: AtomicU64,
     last_price: RwLock<f64>,
     best_bid: RwLock<f64>,
     best_ask: RwLock<f64>,
+    write_version: AtomicU64,
+    updates: RwLock<VecDeque<LevelUpdate>>,
+    stop_orders: RwLock<Vec<StopOrder>>,
 }
 
 impl OrderBook {
-    pub fn new(symbol: String) -> Self {
+    pub fn new(symbol: String, tick_size: f64, lot_size: f64, min_size: f64) -> Self {
         OrderBook {
             symbol,
-            bids: DashMap::new(),
-            asks: DashMap::new(),
+            tick_size,
+            lot_size,
+            min_size,
+            bids: RwLock::new(BTreeMap::new()),
+            asks: RwLock::new(BTreeMap::new()),
             orders: DashMap::new(),
             next_trade_id: AtomicU64::new(1),
             total_volume: AtomicU64::new(0),
             last_price: RwLock::new(0.0),
             best_bid: RwLock::new(0.0),
             best_ask: RwLock::new(f64::MAX),
+            write_version: AtomicU64::new(0),
+            updates: RwLock::new(VecDeque::new()),
+            stop_orders: RwLock::new(Vec::new()),
         }
     }
 
     fn price_to_key(&self, price: f64) -> u64 {
-        (price * 10000.0) as u64 // 4 decimal precision
+        if self.tick_size > 0.0 {
+            (price / self.tick_size).round() as u64
+        } else {
+            (price * 10000.0) as u64 // 4 decimal precision fallback
+        }
     }
 
     fn key_to_price(&self, key: u64) -> f64 {
-        key as f64 / 10000.0
+        if self.tick_size > 0.0 {
+            key as f64 * self.tick_size
+        } else {
+            key as f64 / 10000.0
+        }
+    }
+
+    /// Quantities are exposed as floats at the wasm boundary but matched in
+    /// integer lots, so fill and volume math stays exact instead of drifting
+    /// through repeated `f64` subtraction. `validate` pins every quantity to
+    /// the lot grid, so these conversions round-trip without loss.
+    fn qty_to_lots(&self, quantity: f64) -> u64 {
+        if self.lot_size > 0.0 {
+            (quantity / self.lot_size).round() as u64
+        } else {
+            (quantity * 1e8).round() as u64 // 8-decimal precision fallback
+        }
+    }
+
+    fn lots_to_qty(&self, lots: u64) -> f64 {
+        if self.lot_size > 0.0 {
+            lots as f64 * self.lot_size
+        } else {
+            lots as f64 / 1e8
+        }
+    }
+
+    /// Round a quantity down onto this book's lot grid. The router uses this to
+    /// align a synthetic fill so it passes `validate`'s lot check rather than
+    /// being rejected for an off-lot size.
+    fn floor_to_lot(&self, quantity: f64) -> f64 {
+        if self.lot_size > 0.0 {
+            // Same relative tolerance as `is_multiple`, so an on-grid quantity
+            // whose ratio lands just below a whole lot is not dropped a lot.
+            let ratio = quantity / self.lot_size;
+            (ratio + 1e-9 * ratio.abs().max(1.0)).floor() * self.lot_size
+        } else {
+            quantity
+        }
+    }
+
+    /// Whether `quantity` is below this book's minimum order size.
+    fn below_min(&self, quantity: f64) -> bool {
+        quantity < self.min_size
     }
 
-    pub fn add_order(&self, order: Order) -> Vec<Trade> {
+    fn is_multiple(value: f64, increment: f64) -> bool {
+        if increment <= 0.0 {
+            return true;
+        }
+        let ratio = value / increment;
+        let nearest = ratio.round();
+        // Relative tolerance: a fixed absolute epsilon gets fragile once the
+        // price/tick ratio grows large, so scale the allowance by the ratio.
+        (nearest - ratio).abs() <= 1e-9 * nearest.abs().max(1.0)
+    }
+
+    fn validate(&self, order: &Order) -> Result<(), OrderError> {
+        // Market orders carry no meaningful price, so the tick check only
+        // applies to priced (limit) orders.
+        if order.order_type != OrderType::Market && !Self::is_multiple(order.price, self.tick_size) {
+            return Err(OrderError::OrderInvalidTick);
+        }
+        if !Self::is_multiple(order.quantity, self.lot_size) {
+            return Err(OrderError::OrderInvalidLot);
+        }
+        if order.quantity < self.min_size {
+            return Err(OrderError::OrderBelowMinimum);
+        }
+        Ok(())
+    }
+
+    pub fn add_order(&self, order: Order) -> Result<Vec<Trade>, OrderError> {
+        self.validate(&order)?;
+
         let mut trades = Vec::new();
         let order_id = order.id;
-        
+
         match order.side {
             OrderSide::Buy => {
                 if order.order_type == OrderType::Market {
                     trades.extend(self.match_market_buy(order));
                 } else {
-                    trades.extend(self.match_limit_buy(order));
+                    trades.extend(self.match_limit_buy(order)?);
                 }
             }
             OrderSide::Sell => {
                 if order.order_type == OrderType::Market {
                     trades.extend(self.match_market_sell(order));
                 } else {
-                    trades.extend(self.match_limit_sell(order));
+                    trades.extend(self.match_limit_sell(order)?);
                 }
             }
         }
@@ -146,292 +308,651 @@ impl OrderBook {
             self.update_best_prices();
         }
 
-        trades
+        Ok(trades)
     }
 
-    fn match_market_buy(&self, mut order: Order) -> Vec<Trade> {
+    fn match_market_buy(&self, order: Order) -> Vec<Trade> {
         let mut trades = Vec::new();
-        let mut remaining_qty = order.quantity;
-
-        // Sort asks by price (ascending)
-        let mut sorted_asks: Vec<_> = self.asks.iter().collect();
-        sorted_asks.sort_by(|a, b| a.key().cmp(b.key()));
+        let mut remaining_lots = self.qty_to_lots(order.quantity);
+        let mut touched = Vec::new();
+
+        {
+            let mut asks = self.asks.write();
+            loop {
+                if remaining_lots == 0 {
+                    break;
+                }
+                let price_key = match asks.keys().next() {
+                    Some(key) => *key,
+                    None => break,
+                };
+                let price = self.key_to_price(price_key);
+
+                let mut orders_at_level = std::mem::take(asks.get_mut(&price_key).unwrap());
+                let mut new_level: PriceLevel = SmallVec::new();
+
+                for mut sell_order in orders_at_level.drain(..) {
+                    if remaining_lots == 0 {
+                        new_level.push(sell_order);
+                        continue;
+                    }
+
+                    let sell_lots = self.qty_to_lots(sell_order.quantity);
+                    let trade_lots = remaining_lots.min(sell_lots);
+
+                    trades.push(Trade {
+                        id: self.next_trade_id.fetch_add(1, Ordering::SeqCst),
+                        symbol: order.symbol.clone(),
+                        buy_order_id: order.id,
+                        sell_order_id: sell_order.id,
+                        price,
+                        quantity: self.lots_to_qty(trade_lots),
+                        timestamp: now(),
+                    });
+
+                    remaining_lots -= trade_lots;
+                    let rem_sell_lots = sell_lots - trade_lots;
+                    sell_order.quantity = self.lots_to_qty(rem_sell_lots);
+
+                    *self.last_price.write() = price;
+                    self.total_volume.fetch_add(trade_lots, Ordering::SeqCst);
+
+                    if rem_sell_lots > 0 {
+                        new_level.push(sell_order);
+                    } else {
+                        self.orders.remove(&sell_order.id);
+                    }
+                }
 
-        for ask_entry in sorted_asks {
-            if remaining_qty <= 0.0 {
-                break;
+                if new_level.is_empty() {
+                    asks.remove(&price_key);
+                } else {
+                    asks.insert(price_key, new_level);
+                }
+                touched.push(price_key);
             }
+        }
 
-            let price_key = *ask_entry.key();
-            let price = self.key_to_price(price_key);
-            
-            let mut orders_at_level = ask_entry.value().clone();
-            let mut new_level = SmallVec::new();
+        for price_key in touched {
+            self.emit_update(OrderSide::Sell, price_key);
+        }
 
-            for mut sell_order in orders_at_level.drain(..) {
-                if remaining_qty <= 0.0 {
-                    new_level.push(sell_order);
-                    continue;
-                }
+        if remaining_lots > 0 {
+            console_log!(
+                "Market buy order {} partially filled, {} remaining",
+                order.id,
+                self.lots_to_qty(remaining_lots)
+            );
+        }
 
-                let trade_qty = remaining_qty.min(sell_order.quantity);
-                
-                trades.push(Trade {
-                    id: self.next_trade_id.fetch_add(1, Ordering::SeqCst),
-                    symbol: order.symbol.clone(),
-                    buy_order_id: order.id,
-                    sell_order_id: sell_order.id,
-                    price,
-                    quantity: trade_qty,
-                    timestamp: now(),
-                });
+        trades
+    }
 
-                remaining_qty -= trade_qty;
-                sell_order.quantity -= trade_qty;
-                
-                *self.last_price.write() = price;
-                self.total_volume.fetch_add(trade_qty as u64, Ordering::SeqCst);
+    fn match_market_sell(&self, order: Order) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        let mut remaining_lots = self.qty_to_lots(order.quantity);
+        let mut touched = Vec::new();
+
+        {
+            let mut bids = self.bids.write();
+            loop {
+                if remaining_lots == 0 {
+                    break;
+                }
+                // Best bid is the highest tick: the back of the map.
+                let price_key = match bids.keys().next_back() {
+                    Some(key) => *key,
+                    None => break,
+                };
+                let price = self.key_to_price(price_key);
+
+                let mut orders_at_level = std::mem::take(bids.get_mut(&price_key).unwrap());
+                let mut new_level: PriceLevel = SmallVec::new();
+
+                for mut buy_order in orders_at_level.drain(..) {
+                    if remaining_lots == 0 {
+                        new_level.push(buy_order);
+                        continue;
+                    }
+
+                    let buy_lots = self.qty_to_lots(buy_order.quantity);
+                    let trade_lots = remaining_lots.min(buy_lots);
+
+                    trades.push(Trade {
+                        id: self.next_trade_id.fetch_add(1, Ordering::SeqCst),
+                        symbol: order.symbol.clone(),
+                        buy_order_id: buy_order.id,
+                        sell_order_id: order.id,
+                        price,
+                        quantity: self.lots_to_qty(trade_lots),
+                        timestamp: now(),
+                    });
+
+                    remaining_lots -= trade_lots;
+                    let rem_buy_lots = buy_lots - trade_lots;
+                    buy_order.quantity = self.lots_to_qty(rem_buy_lots);
+
+                    *self.last_price.write() = price;
+                    self.total_volume.fetch_add(trade_lots, Ordering::SeqCst);
+
+                    if rem_buy_lots > 0 {
+                        new_level.push(buy_order);
+                    } else {
+                        self.orders.remove(&buy_order.id);
+                    }
+                }
 
-                if sell_order.quantity > 0.0 {
-                    new_level.push(sell_order);
+                if new_level.is_empty() {
+                    bids.remove(&price_key);
                 } else {
-                    self.orders.remove(&sell_order.id);
+                    bids.insert(price_key, new_level);
                 }
-            }
-
-            if new_level.is_empty() {
-                self.asks.remove(&price_key);
-            } else {
-                self.asks.insert(price_key, new_level);
+                touched.push(price_key);
             }
         }
 
-        if remaining_qty > 0.0 {
-            console_log!("Market buy order {} partially filled, {} remaining", order.id, remaining_qty);
+        for price_key in touched {
+            self.emit_update(OrderSide::Buy, price_key);
         }
 
         trades
     }
 
-    fn match_market_sell(&self, mut order: Order) -> Vec<Trade> {
-        let mut trades = Vec::new();
-        let mut remaining_qty = order.quantity;
+    /// Whether a buy at `limit_price` would immediately take liquidity from the
+    /// resting asks (used to reject Post-Only orders).
+    fn crosses_buy(&self, limit_price: f64) -> bool {
+        let limit_key = self.price_to_key(limit_price);
+        self.asks
+            .read()
+            .keys()
+            .next()
+            .map_or(false, |best| *best <= limit_key)
+    }
 
-        // Sort bids by price (descending)
-        let mut sorted_bids: Vec<_> = self.bids.iter().collect();
-        sorted_bids.sort_by(|a, b| b.key().cmp(a.key()));
+    fn crosses_sell(&self, limit_price: f64) -> bool {
+        let limit_key = self.price_to_key(limit_price);
+        self.bids
+            .read()
+            .keys()
+            .next_back()
+            .map_or(false, |best| *best >= limit_key)
+    }
 
-        for bid_entry in sorted_bids {
-            if remaining_qty <= 0.0 {
+    /// Whether the resting asks can fully fill `quantity` at or below
+    /// `limit_price` (used for Fill-or-Kill pre-scans).
+    fn fillable_buy(&self, limit_price: f64, quantity: f64) -> bool {
+        let mut remaining = self.qty_to_lots(quantity);
+        for (key, level) in self.asks.read().iter() {
+            if self.key_to_price(*key) > limit_price {
                 break;
             }
+            let available: u64 = level.iter().map(|o| self.qty_to_lots(o.quantity)).sum();
+            remaining = remaining.saturating_sub(available);
+            if remaining == 0 {
+                return true;
+            }
+        }
+        false
+    }
 
-            let price_key = *bid_entry.key();
-            let price = self.key_to_price(price_key);
-            
-            let mut orders_at_level = bid_entry.value().clone();
-            let mut new_level = SmallVec::new();
+    fn fillable_sell(&self, limit_price: f64, quantity: f64) -> bool {
+        let mut remaining = self.qty_to_lots(quantity);
+        for (key, level) in self.bids.read().iter().rev() {
+            if self.key_to_price(*key) < limit_price {
+                break;
+            }
+            let available: u64 = level.iter().map(|o| self.qty_to_lots(o.quantity)).sum();
+            remaining = remaining.saturating_sub(available);
+            if remaining == 0 {
+                return true;
+            }
+        }
+        false
+    }
 
-            for mut buy_order in orders_at_level.drain(..) {
-                if remaining_qty <= 0.0 {
-                    new_level.push(buy_order);
-                    continue;
-                }
+    /// Whether the time-in-force pre-checks (Post-Only must not cross,
+    /// Fill-or-Kill must be fully fillable) would reject this order before it
+    /// touches the book. Used to make a failed `modify_order` a no-op rather
+    /// than cancelling the original and then losing the replacement.
+    fn tif_would_reject(&self, order: &Order) -> bool {
+        match (order.side, order.order_type) {
+            (OrderSide::Buy, OrderType::PostOnly) => self.crosses_buy(order.price),
+            (OrderSide::Sell, OrderType::PostOnly) => self.crosses_sell(order.price),
+            (OrderSide::Buy, OrderType::FillOrKill) => {
+                !self.fillable_buy(order.price, order.quantity)
+            }
+            (OrderSide::Sell, OrderType::FillOrKill) => {
+                !self.fillable_sell(order.price, order.quantity)
+            }
+            _ => false,
+        }
+    }
 
-                let trade_qty = remaining_qty.min(buy_order.quantity);
-                
-                trades.push(Trade {
-                    id: self.next_trade_id.fetch_add(1, Ordering::SeqCst),
-                    symbol: order.symbol.clone(),
-                    buy_order_id: buy_order.id,
-                    sell_order_id: order.id,
-                    price,
-                    quantity: trade_qty,
-                    timestamp: now(),
-                });
+    fn match_limit_buy(&self, order: Order) -> Result<Vec<Trade>, OrderError> {
+        // Time-in-force pre-checks: Post-Only must not cross, Fill-or-Kill must
+        // be fully fillable up front or execute nothing.
+        match order.order_type {
+            OrderType::PostOnly if self.crosses_buy(order.price) => {
+                return Err(OrderError::PostOnlyWouldCross);
+            }
+            OrderType::FillOrKill if !self.fillable_buy(order.price, order.quantity) => {
+                return Err(OrderError::FillOrKillUnfillable);
+            }
+            _ => {}
+        }
 
-                remaining_qty -= trade_qty;
-                buy_order.quantity -= trade_qty;
-                
-                *self.last_price.write() = price;
-                self.total_volume.fetch_add(trade_qty as u64, Ordering::SeqCst);
+        let mut trades = Vec::new();
+        let mut remaining_order = order.clone();
+        let mut remaining_lots = self.qty_to_lots(order.quantity);
+        let mut touched = Vec::new();
+
+        {
+            let mut asks = self.asks.write();
+            loop {
+                if remaining_lots == 0 {
+                    break;
+                }
+                let price_key = match asks.keys().next() {
+                    Some(key) => *key,
+                    None => break,
+                };
+                let ask_price = self.key_to_price(price_key);
+                if ask_price > remaining_order.price {
+                    break; // Best ask is above the limit; nothing left to take.
+                }
+
+                let mut orders_at_level = std::mem::take(asks.get_mut(&price_key).unwrap());
+                let mut new_level: PriceLevel = SmallVec::new();
+
+                for mut sell_order in orders_at_level.drain(..) {
+                    if remaining_lots == 0 {
+                        new_level.push(sell_order);
+                        continue;
+                    }
+
+                    let sell_lots = self.qty_to_lots(sell_order.quantity);
+                    let trade_lots = remaining_lots.min(sell_lots);
+
+                    trades.push(Trade {
+                        id: self.next_trade_id.fetch_add(1, Ordering::SeqCst),
+                        symbol: remaining_order.symbol.clone(),
+                        buy_order_id: remaining_order.id,
+                        sell_order_id: sell_order.id,
+                        price: ask_price,
+                        quantity: self.lots_to_qty(trade_lots),
+                        timestamp: now(),
+                    });
+
+                    remaining_lots -= trade_lots;
+                    let rem_sell_lots = sell_lots - trade_lots;
+                    sell_order.quantity = self.lots_to_qty(rem_sell_lots);
+
+                    *self.last_price.write() = ask_price;
+                    self.total_volume.fetch_add(trade_lots, Ordering::SeqCst);
+
+                    if rem_sell_lots > 0 {
+                        new_level.push(sell_order);
+                    } else {
+                        self.orders.remove(&sell_order.id);
+                    }
+                }
 
-                if buy_order.quantity > 0.0 {
-                    new_level.push(buy_order);
+                if new_level.is_empty() {
+                    asks.remove(&price_key);
                 } else {
-                    self.orders.remove(&buy_order.id);
+                    asks.insert(price_key, new_level);
                 }
+                touched.push(price_key);
             }
+        }
 
-            if new_level.is_empty() {
-                self.bids.remove(&price_key);
-            } else {
-                self.bids.insert(price_key, new_level);
-            }
+        for price_key in touched {
+            self.emit_update(OrderSide::Sell, price_key);
         }
 
-        trades
-    }
+        // Rest the remainder only for order types that may post liquidity;
+        // IOC and FOK discard whatever did not match.
+        remaining_order.quantity = self.lots_to_qty(remaining_lots);
+        let rests = matches!(order.order_type, OrderType::Limit | OrderType::PostOnly);
+        if remaining_lots > 0 && rests {
+            let price_key = self.price_to_key(remaining_order.price);
+            self.orders.insert(remaining_order.id, remaining_order.clone());
 
-    fn match_limit_buy(&self, order: Order) -> Vec<Trade> {
-        let mut trades = Vec::new();
-        let mut remaining_order = order.clone();
+            self.bids
+                .write()
+                .entry(price_key)
+                .or_insert_with(SmallVec::new)
+                .push(remaining_order);
+            self.emit_update(OrderSide::Buy, price_key);
+        }
 
-        // Match against existing asks
-        let mut sorted_asks: Vec<_> = self.asks.iter().collect();
-        sorted_asks.sort_by(|a, b| a.key().cmp(b.key()));
+        Ok(trades)
+    }
 
-        for ask_entry in sorted_asks {
-            let price_key = *ask_entry.key();
-            let ask_price = self.key_to_price(price_key);
-            
-            if ask_price > remaining_order.price {
-                break; // No more matching asks
+    fn match_limit_sell(&self, order: Order) -> Result<Vec<Trade>, OrderError> {
+        match order.order_type {
+            OrderType::PostOnly if self.crosses_sell(order.price) => {
+                return Err(OrderError::PostOnlyWouldCross);
             }
-
-            if remaining_order.quantity <= 0.0 {
-                break;
+            OrderType::FillOrKill if !self.fillable_sell(order.price, order.quantity) => {
+                return Err(OrderError::FillOrKillUnfillable);
             }
+            _ => {}
+        }
 
-            let mut orders_at_level = ask_entry.value().clone();
-            let mut new_level = SmallVec::new();
-
-            for mut sell_order in orders_at_level.drain(..) {
-                if remaining_order.quantity <= 0.0 {
-                    new_level.push(sell_order);
-                    continue;
+        let mut trades = Vec::new();
+        let mut remaining_order = order.clone();
+        let mut remaining_lots = self.qty_to_lots(order.quantity);
+        let mut touched = Vec::new();
+
+        {
+            let mut bids = self.bids.write();
+            loop {
+                if remaining_lots == 0 {
+                    break;
+                }
+                // Best bid is the highest tick: the back of the map.
+                let price_key = match bids.keys().next_back() {
+                    Some(key) => *key,
+                    None => break,
+                };
+                let bid_price = self.key_to_price(price_key);
+                if bid_price < remaining_order.price {
+                    break; // Best bid is below the limit; nothing left to take.
                 }
 
-                let trade_qty = remaining_order.quantity.min(sell_order.quantity);
-                
-                trades.push(Trade {
-                    id: self.next_trade_id.fetch_add(1, Ordering::SeqCst),
-                    symbol: remaining_order.symbol.clone(),
-                    buy_order_id: remaining_order.id,
-                    sell_order_id: sell_order.id,
-                    price: ask_price,
-                    quantity: trade_qty,
-                    timestamp: now(),
-                });
-
-                remaining_order.quantity -= trade_qty;
-                sell_order.quantity -= trade_qty;
-                
-                *self.last_price.write() = ask_price;
-                self.total_volume.fetch_add(trade_qty as u64, Ordering::SeqCst);
+                let mut orders_at_level = std::mem::take(bids.get_mut(&price_key).unwrap());
+                let mut new_level: PriceLevel = SmallVec::new();
+
+                for mut buy_order in orders_at_level.drain(..) {
+                    if remaining_lots == 0 {
+                        new_level.push(buy_order);
+                        continue;
+                    }
+
+                    let buy_lots = self.qty_to_lots(buy_order.quantity);
+                    let trade_lots = remaining_lots.min(buy_lots);
+
+                    trades.push(Trade {
+                        id: self.next_trade_id.fetch_add(1, Ordering::SeqCst),
+                        symbol: remaining_order.symbol.clone(),
+                        buy_order_id: buy_order.id,
+                        sell_order_id: remaining_order.id,
+                        price: bid_price,
+                        quantity: self.lots_to_qty(trade_lots),
+                        timestamp: now(),
+                    });
+
+                    remaining_lots -= trade_lots;
+                    let rem_buy_lots = buy_lots - trade_lots;
+                    buy_order.quantity = self.lots_to_qty(rem_buy_lots);
+
+                    *self.last_price.write() = bid_price;
+                    self.total_volume.fetch_add(trade_lots, Ordering::SeqCst);
+
+                    if rem_buy_lots > 0 {
+                        new_level.push(buy_order);
+                    } else {
+                        self.orders.remove(&buy_order.id);
+                    }
+                }
 
-                if sell_order.quantity > 0.0 {
-                    new_level.push(sell_order);
+                if new_level.is_empty() {
+                    bids.remove(&price_key);
                 } else {
-                    self.orders.remove(&sell_order.id);
+                    bids.insert(price_key, new_level);
                 }
+                touched.push(price_key);
             }
+        }
 
-            if new_level.is_empty() {
-                self.asks.remove(&price_key);
-            } else {
-                self.asks.insert(price_key, new_level);
-            }
+        for price_key in touched {
+            self.emit_update(OrderSide::Buy, price_key);
         }
 
-        // Add remaining quantity to order book
-        if remaining_order.quantity > 0.0 {
+        remaining_order.quantity = self.lots_to_qty(remaining_lots);
+        let rests = matches!(order.order_type, OrderType::Limit | OrderType::PostOnly);
+        if remaining_lots > 0 && rests {
             let price_key = self.price_to_key(remaining_order.price);
             self.orders.insert(remaining_order.id, remaining_order.clone());
-            
-            let mut level = self.bids.entry(price_key).or_insert_with(SmallVec::new);
-            level.push(remaining_order);
+
+            self.asks
+                .write()
+                .entry(price_key)
+                .or_insert_with(SmallVec::new)
+                .push(remaining_order);
+            self.emit_update(OrderSide::Sell, price_key);
         }
 
-        trades
+        Ok(trades)
     }
 
-    fn match_limit_sell(&self, order: Order) -> Vec<Trade> {
-        let mut trades = Vec::new();
-        let mut remaining_order = order.clone();
+    fn add_stop_order(&self, stop: StopOrder) {
+        self.stop_orders.write().push(stop);
+    }
 
-        // Match against existing bids
-        let mut sorted_bids: Vec<_> = self.bids.iter().collect();
-        sorted_bids.sort_by(|a, b| b.key().cmp(a.key()));
+    /// Fire every triggerable stop against the current `last_price`, converting
+    /// each to a market or limit order and feeding it back through `add_order`.
+    /// Triggered fills move `last_price` again, so we loop until no further
+    /// stops fire, bounded to guard against runaway cascades.
+    fn process_stops(&self) {
+        const MAX_ITERATIONS: usize = 32;
+
+        for _ in 0..MAX_ITERATIONS {
+            let last = *self.last_price.read();
+            if last <= 0.0 {
+                break; // No trade has printed yet; nothing to trigger against.
+            }
 
-        for bid_entry in sorted_bids {
-            let price_key = *bid_entry.key();
-            let bid_price = self.key_to_price(price_key);
-            
-            if bid_price < remaining_order.price {
-                break; // No more matching bids
+            let mut triggered = Vec::new();
+            {
+                let mut stops = self.stop_orders.write();
+                stops.retain(|stop| {
+                    let fires = match stop.side {
+                        OrderSide::Buy => last >= stop.trigger_price,
+                        OrderSide::Sell => last <= stop.trigger_price,
+                    };
+                    if fires {
+                        triggered.push(stop.clone());
+                    }
+                    !fires
+                });
             }
 
-            if remaining_order.quantity <= 0.0 {
+            if triggered.is_empty() {
                 break;
             }
 
-            let mut orders_at_level = bid_entry.value().clone();
-            let mut new_level = SmallVec::new();
+            for stop in triggered {
+                let order_type = if stop.limit_price > 0.0 {
+                    OrderType::Limit
+                } else {
+                    OrderType::Market
+                };
+                let order = Order {
+                    id: stop.id,
+                    symbol: self.symbol.clone(),
+                    side: stop.side,
+                    order_type,
+                    quantity: stop.quantity,
+                    price: stop.limit_price,
+                    timestamp: now(),
+                    user_id: stop.user_id,
+                };
+                let _ = self.add_order(order);
+            }
+        }
+    }
 
-            for mut buy_order in orders_at_level.drain(..) {
-                if remaining_order.quantity <= 0.0 {
-                    new_level.push(buy_order);
-                    continue;
+    pub fn cancel_order(&self, order_id: u64) -> bool {
+        let order = match self.orders.remove(&order_id) {
+            Some((_, order)) => order,
+            None => return false,
+        };
+
+        let price_key = self.price_to_key(order.price);
+        let book = match order.side {
+            OrderSide::Buy => &self.bids,
+            OrderSide::Sell => &self.asks,
+        };
+
+        {
+            let mut book = book.write();
+            if let Some(level) = book.get_mut(&price_key) {
+                level.retain(|o| o.id != order_id);
+                if level.is_empty() {
+                    book.remove(&price_key);
                 }
+            }
+        }
 
-                let trade_qty = remaining_order.quantity.min(buy_order.quantity);
-                
-                trades.push(Trade {
-                    id: self.next_trade_id.fetch_add(1, Ordering::SeqCst),
-                    symbol: remaining_order.symbol.clone(),
-                    buy_order_id: buy_order.id,
-                    sell_order_id: remaining_order.id,
-                    price: bid_price,
-                    quantity: trade_qty,
-                    timestamp: now(),
-                });
+        self.emit_update(order.side, price_key);
+        self.update_best_prices();
+        true
+    }
 
-                remaining_order.quantity -= trade_qty;
-                buy_order.quantity -= trade_qty;
-                
-                *self.last_price.write() = bid_price;
-                self.total_volume.fetch_add(trade_qty as u64, Ordering::SeqCst);
+    pub fn modify_order(&self, order_id: u64, new_quantity: f64, new_price: f64) -> bool {
+        let existing = match self.orders.get(&order_id).map(|entry| entry.clone()) {
+            Some(order) => order,
+            None => return false,
+        };
+
+        // Reduce-only amends keep their place in the queue; a price change or a
+        // size increase loses priority and re-enters at the tail of the level.
+        let reduce_only = new_price == existing.price && new_quantity <= existing.quantity;
+
+        // Validate the amended order up front so a malformed amend (bad tick,
+        // off-lot, or below the minimum) is rejected without destroying the
+        // resting order. This also guards the reduce-only branch, which would
+        // otherwise let a reduce below `min_size` or off the lot grid through.
+        let mut amended = existing.clone();
+        amended.quantity = new_quantity;
+        amended.price = new_price;
+        if self.validate(&amended).is_err() {
+            return false;
+        }
 
-                if buy_order.quantity > 0.0 {
-                    new_level.push(buy_order);
-                } else {
-                    self.orders.remove(&buy_order.id);
+        if reduce_only {
+            let price_key = self.price_to_key(existing.price);
+            let book = match existing.side {
+                OrderSide::Buy => &self.bids,
+                OrderSide::Sell => &self.asks,
+            };
+
+            if let Some(level) = book.write().get_mut(&price_key) {
+                if let Some(resting) = level.iter_mut().find(|o| o.id == order_id) {
+                    resting.quantity = new_quantity;
                 }
             }
+            if let Some(mut order) = self.orders.get_mut(&order_id) {
+                order.quantity = new_quantity;
+            }
 
-            if new_level.is_empty() {
-                self.bids.remove(&price_key);
-            } else {
-                self.bids.insert(price_key, new_level);
+            self.emit_update(existing.side, price_key);
+            self.update_best_prices();
+        } else {
+            // Pre-check time-in-force rejections (e.g. a resting Post-Only
+            // amended to a crossing price) before cancelling, so a modify that
+            // cannot be re-posted leaves the original order untouched instead
+            // of destroying it.
+            if self.tif_would_reject(&amended) {
+                return false;
+            }
+
+            self.cancel_order(order_id);
+
+            let mut order = amended;
+            order.timestamp = now();
+            if self.add_order(order).is_err() {
+                return false;
             }
         }
 
-        // Add remaining quantity to order book
-        if remaining_order.quantity > 0.0 {
-            let price_key = self.price_to_key(remaining_order.price);
-            self.orders.insert(remaining_order.id, remaining_order.clone());
-            
-            let mut level = self.asks.entry(price_key).or_insert_with(SmallVec::new);
-            level.push(remaining_order);
+        true
+    }
+
+    fn level_total(&self, side: OrderSide, price_key: u64) -> f64 {
+        let book = match side {
+            OrderSide::Buy => &self.bids,
+            OrderSide::Sell => &self.asks,
+        };
+        book.read()
+            .get(&price_key)
+            .map(|level| self.lots_to_qty(level.iter().map(|o| self.qty_to_lots(o.quantity)).sum()))
+            .unwrap_or(0.0)
+    }
+
+    /// Record the new aggregated quantity at `price_key` as a delta. Must be
+    /// called with no outstanding guard on the affected book shard.
+    fn emit_update(&self, side: OrderSide, price_key: u64) {
+        let new_total_qty = self.level_total(side, price_key);
+        let write_version = self.write_version.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let update = LevelUpdate {
+            side,
+            price: self.key_to_price(price_key),
+            new_total_qty,
+            write_version,
+        };
+
+        let mut buf = self.updates.write();
+        if buf.len() >= UPDATE_RING_CAPACITY {
+            buf.pop_front();
         }
+        buf.push_back(update);
+    }
 
-        trades
+    /// Ordered deltas with a write version strictly greater than `since_version`.
+    ///
+    /// If the next delta the client expects (`since_version + 1`) has already
+    /// been evicted from the ring buffer, the returned batch is incomplete;
+    /// `must_resync` flags this so the client re-syncs instead of silently
+    /// applying a partial delta set on top of a stale book.
+    pub fn updates_since(&self, since_version: u64) -> UpdateBatch {
+        let buf = self.updates.read();
+        let must_resync = buf
+            .front()
+            .map_or(false, |oldest| oldest.write_version > since_version + 1);
+        let updates = buf
+            .iter()
+            .filter(|u| u.write_version > since_version)
+            .cloned()
+            .collect();
+        UpdateBatch { must_resync, updates }
     }
 
     fn update_best_prices(&self) {
-        // Update best bid
-        if let Some(best_bid_entry) = self.bids.iter().max_by_key(|entry| *entry.key()) {
-            *self.best_bid.write() = self.key_to_price(*best_bid_entry.key());
-        } else {
-            *self.best_bid.write() = 0.0;
-        }
+        // Best bid/ask are the last and first entries of the ordered ladders,
+        // so each is an O(log n) lookup instead of a full scan.
+        *self.best_bid.write() = self
+            .bids
+            .read()
+            .keys()
+            .next_back()
+            .map(|key| self.key_to_price(*key))
+            .unwrap_or(0.0);
+
+        *self.best_ask.write() = self
+            .asks
+            .read()
+            .keys()
+            .next()
+            .map(|key| self.key_to_price(*key))
+            .unwrap_or(f64::MAX);
+    }
 
-        // Update best ask
-        if let Some(best_ask_entry) = self.asks.iter().min_by_key(|entry| *entry.key()) {
-            *self.best_ask.write() = self.key_to_price(*best_ask_entry.key());
-        } else {
-            *self.best_ask.write() = f64::MAX;
+    /// Best price an incoming order of `side` could take, paired with the total
+    /// resting quantity at that level. `None` when the opposing side is empty.
+    pub fn best_opposing(&self, side: OrderSide) -> Option<(f64, f64)> {
+        match side {
+            OrderSide::Buy => self.asks.read().iter().next().map(|(key, level)| {
+                let lots: u64 = level.iter().map(|o| self.qty_to_lots(o.quantity)).sum();
+                (self.key_to_price(*key), self.lots_to_qty(lots))
+            }),
+            OrderSide::Sell => self.bids.read().iter().next_back().map(|(key, level)| {
+                let lots: u64 = level.iter().map(|o| self.qty_to_lots(o.quantity)).sum();
+                (self.key_to_price(*key), self.lots_to_qty(lots))
+            }),
         }
     }
 
@@ -472,8 +993,13 @@ impl TradingEngine {
     }
 
     #[wasm_bindgen]
-    pub fn add_symbol(&self, symbol: &str) {
-        let orderbook = Arc::new(OrderBook::new(symbol.to_string()));
+    pub fn add_symbol(&self, symbol: &str, tick_size: f64, lot_size: f64, min_size: f64) {
+        let orderbook = Arc::new(OrderBook::new(
+            symbol.to_string(),
+            tick_size,
+            lot_size,
+            min_size,
+        ));
         self.orderbooks.insert(symbol.to_string(), orderbook);
         console_log!("ðŸ“ˆ Added orderbook for symbol: {}", symbol);
     }
@@ -503,8 +1029,14 @@ impl TradingEngine {
                 user_id,
             );
 
-            let trades = orderbook.add_order(order);
-            
+            let trades = match orderbook.add_order(order) {
+                Ok(trades) => trades,
+                Err(err) => return serde_json::json!({ "error": err }).to_string(),
+            };
+
+            // Any prints from this order may have crossed resting stop triggers.
+            orderbook.process_stops();
+
             self.processed_orders.fetch_add(1, Ordering::SeqCst);
             self.total_trades.fetch_add(trades.len() as u64, Ordering::SeqCst);
             
@@ -520,25 +1052,225 @@ impl TradingEngine {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn cancel_order(&self, symbol: &str, order_id: u64) -> bool {
+        if let Some(orderbook) = self.orderbooks.get(symbol) {
+            orderbook.cancel_order(order_id)
+        } else {
+            false
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn modify_order(
+        &self,
+        symbol: &str,
+        order_id: u64,
+        new_quantity: f64,
+        new_price: f64,
+    ) -> bool {
+        if let Some(orderbook) = self.orderbooks.get(symbol) {
+            orderbook.modify_order(order_id, new_quantity, new_price)
+        } else {
+            false
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn place_stop_order(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        trigger_price: f64,
+        limit_price: f64,
+        quantity: f64,
+        user_id: u32,
+    ) -> String {
+        if let Some(orderbook) = self.orderbooks.get(symbol) {
+            let order_id = self.next_order_id.fetch_add(1, Ordering::SeqCst);
+
+            orderbook.add_stop_order(StopOrder {
+                id: order_id,
+                side,
+                trigger_price,
+                limit_price,
+                quantity,
+                user_id,
+            });
+
+            // A stop may already be triggerable against the last traded price.
+            orderbook.process_stops();
+
+            serde_json::json!({ "stop_order_id": order_id }).to_string()
+        } else {
+            format!("Error: Symbol {} not found", symbol)
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn route_order(
+        &self,
+        symbols: Vec<String>,
+        side: OrderSide,
+        quantity: f64,
+        limit_price: f64,
+        user_id: u32,
+    ) -> String {
+        // Bound the number of routing hops; each hop takes one venue's best
+        // level, so this caps total work across fragmented books.
+        const MAX_STEPS: usize = 10_000;
+
+        let mut remaining = quantity;
+        let mut breakdown: HashMap<String, Vec<Trade>> = HashMap::new();
+        // A non-null reason is surfaced to the caller so an aligned-away or
+        // below-minimum remainder is not mistaken for exhausted liquidity.
+        let mut rejection: Option<String> = None;
+
+        for _ in 0..MAX_STEPS {
+            if remaining <= 0.0 {
+                break;
+            }
+
+            // Pick the venue currently offering the best price within the limit.
+            let mut best: Option<(String, f64)> = None;
+            for symbol in &symbols {
+                if let Some(orderbook) = self.orderbooks.get(symbol) {
+                    if let Some((price, _qty)) = orderbook.best_opposing(side) {
+                        let within_limit = match side {
+                            OrderSide::Buy => price <= limit_price,
+                            OrderSide::Sell => price >= limit_price,
+                        };
+                        if !within_limit {
+                            continue;
+                        }
+                        let improves = match &best {
+                            None => true,
+                            Some((_, best_price)) => match side {
+                                OrderSide::Buy => price < *best_price,
+                                OrderSide::Sell => price > *best_price,
+                            },
+                        };
+                        if improves {
+                            best = Some((symbol.clone(), price));
+                        }
+                    }
+                }
+            }
+
+            let (symbol, best_price) = match best {
+                Some(best) => best,
+                None => break, // No venue has liquidity within the limit.
+            };
+
+            let orderbook = match self.orderbooks.get(&symbol) {
+                Some(orderbook) => orderbook,
+                None => break,
+            };
+
+            // Take only this venue's best level: capping the quantity to the
+            // level's depth keeps the IOC from sweeping into worse prices that
+            // another book might beat on the next hop. Align the fill down onto
+            // the venue's lot grid so the synthetic order passes validation.
+            let avail = orderbook
+                .best_opposing(side)
+                .map(|(_, qty)| qty)
+                .unwrap_or(0.0);
+            let fill_qty = orderbook.floor_to_lot(remaining.min(avail));
+            if fill_qty <= 0.0 {
+                // The remainder is smaller than one lot on this venue.
+                rejection = Some(format!(
+                    "remaining {} below lot_size on {}",
+                    remaining, symbol
+                ));
+                break;
+            }
+            if orderbook.below_min(fill_qty) {
+                // The remainder is real liquidity the router can't route: it is
+                // below the venue's minimum, not absent.
+                rejection = Some(format!(
+                    "remaining {} below min_size on {}",
+                    fill_qty, symbol
+                ));
+                break;
+            }
+
+            let order_id = self.next_order_id.fetch_add(1, Ordering::SeqCst);
+            // Price at the venue's best level (already on the tick grid and
+            // within the limit) so the synthetic IOC never trips the tick check
+            // on an off-grid `limit_price`.
+            let order = Order::new(
+                order_id,
+                symbol.clone(),
+                side,
+                OrderType::ImmediateOrCancel,
+                fill_qty,
+                best_price,
+                user_id,
+            );
+
+            let trades = match orderbook.add_order(order) {
+                Ok(trades) => trades,
+                Err(err) => {
+                    rejection = Some(format!("{} rejected on {}: {:?}", fill_qty, symbol, err));
+                    break;
+                }
+            };
+            orderbook.process_stops();
+
+            self.processed_orders.fetch_add(1, Ordering::SeqCst);
+            self.total_trades.fetch_add(trades.len() as u64, Ordering::SeqCst);
+
+            let filled: f64 = trades.iter().map(|t| t.quantity).sum();
+            remaining -= filled;
+            breakdown.entry(symbol).or_default().extend(trades);
+
+            if filled <= 0.0 {
+                break; // Guard against a venue that reports depth but fills nothing.
+            }
+        }
+
+        let fills: Vec<_> = breakdown
+            .into_iter()
+            .map(|(symbol, trades)| {
+                let quantity: f64 = trades.iter().map(|t| t.quantity).sum();
+                serde_json::json!({
+                    "symbol": symbol,
+                    "quantity": quantity,
+                    "trades": trades
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "filled": quantity - remaining,
+            "remaining": remaining,
+            "fills": fills,
+            "rejection": rejection
+        })
+        .to_string()
+    }
+
     #[wasm_bindgen]
     pub fn get_orderbook(&self, symbol: &str) -> String {
         if let Some(orderbook) = self.orderbooks.get(symbol) {
             let mut bids: Vec<_> = orderbook.bids
+                .read()
                 .iter()
-                .map(|entry| {
-                    let price = orderbook.key_to_price(*entry.key());
-                    let total_qty: f64 = entry.value().iter().map(|o| o.quantity).sum();
-                    (price, total_qty, entry.value().len())
+                .map(|(key, level)| {
+                    let price = orderbook.key_to_price(*key);
+                    let total_qty: f64 = level.iter().map(|o| o.quantity).sum();
+                    (price, total_qty, level.len())
                 })
                 .collect();
             bids.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
 
             let mut asks: Vec<_> = orderbook.asks
+                .read()
                 .iter()
-                .map(|entry| {
-                    let price = orderbook.key_to_price(*entry.key());
-                    let total_qty: f64 = entry.value().iter().map(|o| o.quantity).sum();
-                    (price, total_qty, entry.value().len())
+                .map(|(key, level)| {
+                    let price = orderbook.key_to_price(*key);
+                    let total_qty: f64 = level.iter().map(|o| o.quantity).sum();
+                    (price, total_qty, level.len())
                 })
                 .collect();
             asks.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
@@ -549,7 +1281,7 @@ impl TradingEngine {
                 "asks": asks.into_iter().take(20).collect::<Vec<_>>(),
                 "last_price": *orderbook.last_price.read(),
                 "spread": orderbook.get_spread(),
-                "total_volume": orderbook.total_volume.load(Ordering::SeqCst)
+                "total_volume": orderbook.lots_to_qty(orderbook.total_volume.load(Ordering::SeqCst))
             });
 
             data.to_string()
@@ -558,6 +1290,61 @@ impl TradingEngine {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn get_checkpoint(&self, symbol: &str) -> String {
+        if let Some(orderbook) = self.orderbooks.get(symbol) {
+            // Snapshot both ladders and the write version under a single
+            // critical section: holding the read guards blocks any concurrent
+            // mutation, so the version can never describe a ladder the
+            // checkpoint didn't capture (which would let `poll_updates` skip
+            // the interleaved delta and desync the client).
+            let bids_guard = orderbook.bids.read();
+            let asks_guard = orderbook.asks.read();
+            let write_version = orderbook.write_version.load(Ordering::SeqCst);
+
+            let mut bids: Vec<_> = bids_guard
+                .iter()
+                .map(|(key, level)| {
+                    let price = orderbook.key_to_price(*key);
+                    let total_qty: f64 = level.iter().map(|o| o.quantity).sum();
+                    (price, total_qty)
+                })
+                .collect();
+            bids.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+
+            let mut asks: Vec<_> = asks_guard
+                .iter()
+                .map(|(key, level)| {
+                    let price = orderbook.key_to_price(*key);
+                    let total_qty: f64 = level.iter().map(|o| o.quantity).sum();
+                    (price, total_qty)
+                })
+                .collect();
+            asks.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            let data = serde_json::json!({
+                "symbol": symbol,
+                "bids": bids,
+                "asks": asks,
+                "write_version": write_version
+            });
+
+            data.to_string()
+        } else {
+            format!("{{\"error\": \"Symbol {} not found\"}}", symbol)
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn poll_updates(&self, symbol: &str, since_version: u64) -> String {
+        if let Some(orderbook) = self.orderbooks.get(symbol) {
+            let batch = orderbook.updates_since(since_version);
+            serde_json::to_string(&batch).unwrap_or_else(|_| "[]".to_string())
+        } else {
+            format!("{{\"error\": \"Symbol {} not found\"}}", symbol)
+        }
+    }
+
     #[wasm_bindgen]
     pub fn get_stats(&self) -> String {
         let stats = serde_json::json!({
@@ -601,7 +1388,127 @@ impl TradingEngine {
         });
         
         console_log!("âœ… Benchmark complete: {:.0} orders/sec (target: 1M/sec)", orders_per_second);
-        
+
         benchmark.to_string()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(id: u64, side: OrderSide, order_type: OrderType, quantity: f64, price: f64) -> Order {
+        Order {
+            id,
+            symbol: "TEST".to_string(),
+            side,
+            order_type,
+            quantity,
+            price,
+            timestamp: 0.0,
+            user_id: 1,
+        }
+    }
+
+    // A sequence of fractional-lot partial fills must leave no sub-lot dust:
+    // the resting size is tracked in integer lots, so three 0.1 takes fully
+    // drain a 0.3 level with nothing resting behind.
+    #[test]
+    fn partial_fill_keeps_lots_exact() {
+        let book = OrderBook::new("TEST".to_string(), 1.0, 0.1, 0.1);
+        book.add_order(order(1, OrderSide::Sell, OrderType::Limit, 0.3, 10.0))
+            .unwrap();
+
+        for id in 2..=4 {
+            let trades = book
+                .add_order(order(id, OrderSide::Buy, OrderType::Limit, 0.1, 10.0))
+                .unwrap();
+            assert_eq!(trades.len(), 1);
+        }
+
+        assert!(book.asks.read().is_empty());
+        assert!(book.orders.is_empty());
+    }
+
+    // Fill-or-Kill executes in full or not at all.
+    #[test]
+    fn fok_is_all_or_nothing() {
+        let book = OrderBook::new("TEST".to_string(), 1.0, 1.0, 1.0);
+        book.add_order(order(1, OrderSide::Sell, OrderType::Limit, 2.0, 10.0))
+            .unwrap();
+
+        let unfillable = book.add_order(order(2, OrderSide::Buy, OrderType::FillOrKill, 5.0, 10.0));
+        assert!(matches!(unfillable, Err(OrderError::FillOrKillUnfillable)));
+        // The resting liquidity is untouched by the rejected FOK.
+        assert_eq!(book.best_opposing(OrderSide::Buy).map(|(_, q)| q), Some(2.0));
+
+        let filled = book
+            .add_order(order(3, OrderSide::Buy, OrderType::FillOrKill, 2.0, 10.0))
+            .unwrap();
+        assert_eq!(filled.len(), 1);
+        assert!(book.asks.read().is_empty());
+    }
+
+    // Post-Only is rejected when it would cross, otherwise it rests passively.
+    #[test]
+    fn post_only_rejects_cross_else_rests() {
+        let book = OrderBook::new("TEST".to_string(), 1.0, 1.0, 1.0);
+        book.add_order(order(1, OrderSide::Sell, OrderType::Limit, 1.0, 10.0))
+            .unwrap();
+
+        let crossing = book.add_order(order(2, OrderSide::Buy, OrderType::PostOnly, 1.0, 10.0));
+        assert!(matches!(crossing, Err(OrderError::PostOnlyWouldCross)));
+
+        let rested = book
+            .add_order(order(3, OrderSide::Buy, OrderType::PostOnly, 1.0, 9.0))
+            .unwrap();
+        assert!(rested.is_empty());
+        assert_eq!(book.best_opposing(OrderSide::Sell).map(|(p, _)| p), Some(9.0));
+    }
+
+    // A buy-stop fires once a trade lifts `last_price` to its trigger, and the
+    // converted market order sweeps the remaining liquidity.
+    #[test]
+    fn stop_order_triggers_on_trade() {
+        let engine = TradingEngine::new();
+        engine.add_symbol("TEST", 1.0, 1.0, 1.0);
+        engine.place_order("TEST", OrderSide::Sell, OrderType::Limit, 1.0, 10.0, 1);
+        engine.place_order("TEST", OrderSide::Sell, OrderType::Limit, 1.0, 11.0, 1);
+
+        engine.place_stop_order("TEST", OrderSide::Buy, 10.0, 0.0, 1.0, 2);
+        engine.place_order("TEST", OrderSide::Buy, OrderType::Limit, 1.0, 10.0, 3);
+
+        let book = engine.orderbooks.get("TEST").unwrap();
+        assert!(book.asks.read().is_empty(), "stop should sweep the 11.0 ask");
+        assert!((*book.last_price.read() - 11.0).abs() < 1e-9);
+    }
+
+    // The router walks the best price across books, taking A's cheaper level
+    // first and spilling the remainder onto B.
+    #[test]
+    fn route_splits_across_books() {
+        let engine = TradingEngine::new();
+        engine.add_symbol("A", 1.0, 1.0, 1.0);
+        engine.add_symbol("B", 1.0, 1.0, 1.0);
+        engine.place_order("A", OrderSide::Sell, OrderType::Limit, 2.0, 10.0, 1);
+        engine.place_order("B", OrderSide::Sell, OrderType::Limit, 3.0, 11.0, 1);
+
+        let out = engine.route_order(
+            vec!["A".to_string(), "B".to_string()],
+            OrderSide::Buy,
+            4.0,
+            11.0,
+            9,
+        );
+        let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+        assert!((v["filled"].as_f64().unwrap() - 4.0).abs() < 1e-9);
+        assert!(v["remaining"].as_f64().unwrap().abs() < 1e-9);
+        assert!(v["rejection"].is_null());
+
+        let a = engine.orderbooks.get("A").unwrap();
+        let b = engine.orderbooks.get("B").unwrap();
+        assert!(a.asks.read().is_empty());
+        assert_eq!(b.best_opposing(OrderSide::Buy).map(|(_, q)| q), Some(1.0));
+    }
 }
\ No newline at end of file